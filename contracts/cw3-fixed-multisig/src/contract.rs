@@ -0,0 +1,970 @@
+use std::cmp::Ordering;
+
+use cosmwasm_std::{
+    to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Order, Response, StdResult, WasmMsg,
+};
+
+use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
+use cw3::{
+    ProposalListResponse, ProposalResponse, Status, ThresholdResponse, Vote, VoteInfo,
+    VoteListResponse, VoteResponse, VoterDetail, VoterListResponse, VoterResponse,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{maybe_addr, Expiration};
+
+use crate::error::ContractError;
+use crate::msg::{
+    DepositInfo, DepositToken, ExecuteMsg, InstantiateMsg, MultiOptionMsg, MultiProposalResponse,
+    QueryMsg, TreasuryResponse,
+};
+use crate::schulze::{schulze_winner, Ranking};
+use crate::state::{
+    next_id, Ballot, CheckedDepositInfo, Config, MultiOption, MultiProposal, Proposal,
+    ProposalDeposit, RankedBallot, Votes, BALLOTS, CONFIG, MULTI_PROPOSALS, PROPOSALS,
+    RANKED_BALLOTS, VOTERS,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw3-fixed-multisig";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if msg.voters.is_empty() {
+        return Err(ContractError::NoVoters {});
+    }
+    let total_weight = msg.voters.iter().map(|v| v.weight).sum();
+
+    msg.threshold.validate(total_weight)?;
+
+    if let Some(veto_threshold) = msg.veto_threshold {
+        if veto_threshold.is_zero() || veto_threshold > Decimal::one() {
+            return Err(ContractError::InvalidVetoThreshold {});
+        }
+    }
+
+    let deposit_info = msg
+        .deposit_info
+        .map(|d| check_deposit_info(deps.as_ref(), d))
+        .transpose()?;
+
+    let cfg = Config {
+        threshold: msg.threshold,
+        total_weight,
+        max_voting_period: msg.max_voting_period,
+        deposit_info,
+        veto_threshold: msg.veto_threshold,
+        multi_proposal_quorum: msg.multi_proposal_quorum,
+    };
+    CONFIG.save(deps.storage, &cfg)?;
+
+    for voter in msg.voters.iter() {
+        let addr = deps.api.addr_validate(&voter.addr)?;
+        VOTERS.save(deps.storage, &addr, &voter.weight)?;
+    }
+
+    Ok(Response::default())
+}
+
+fn check_deposit_info(deps: Deps, deposit_info: DepositInfo) -> StdResult<CheckedDepositInfo> {
+    let token = match deposit_info.token {
+        DepositToken::Native { denom } => DepositToken::Native { denom },
+        DepositToken::Cw20 { address } => DepositToken::Cw20 {
+            address: deps.api.addr_validate(&address)?.into_string(),
+        },
+    };
+    Ok(CheckedDepositInfo {
+        token,
+        amount: deposit_info.amount,
+        refund_failed_proposals: deposit_info.refund_failed_proposals,
+    })
+}
+
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Propose {
+            title,
+            description,
+            msgs,
+            latest,
+        } => execute_propose(deps, env, info, title, description, msgs, latest),
+        ExecuteMsg::Vote { proposal_id, vote } => execute_vote(deps, env, info, proposal_id, vote),
+        ExecuteMsg::Execute { proposal_id } => execute_execute(deps, env, info, proposal_id),
+        ExecuteMsg::Close { proposal_id } => execute_close(deps, env, info, proposal_id),
+        ExecuteMsg::Deposit {} => execute_deposit(info),
+        ExecuteMsg::ProposeMultiple {
+            title,
+            description,
+            options,
+            latest,
+        } => execute_propose_multiple(deps, env, info, title, description, options, latest),
+        ExecuteMsg::VoteRanked {
+            proposal_id,
+            ranking,
+        } => execute_vote_ranked(deps, env, info, proposal_id, ranking),
+        ExecuteMsg::ExecuteMultiple { proposal_id } => {
+            execute_execute_multiple(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::CloseMultiple { proposal_id } => {
+            execute_close_multiple(deps, env, info, proposal_id)
+        }
+    }
+}
+
+/// Anyone can send native funds into the treasury this way; the `info.funds`
+/// attached to the call are already credited to the contract's bank balance
+/// by the chain, so there is nothing left to move.
+pub fn execute_deposit(info: MessageInfo) -> Result<Response, ContractError> {
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("sender", info.sender)
+        .add_attribute(
+            "funds",
+            info.funds
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+}
+
+pub fn execute_propose(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    title: String,
+    description: String,
+    msgs: Vec<CosmosMsg<Empty>>,
+    latest: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    // only members of the multisig can create a proposal
+    let vote_power = VOTERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // max expires also used as default
+    let max_expires = cfg.max_voting_period.after(&env.block);
+    let mut expires = latest.unwrap_or(max_expires);
+    let comp = expires.partial_cmp(&max_expires);
+    if let Some(Ordering::Greater) = comp {
+        expires = max_expires;
+    } else if comp.is_none() {
+        return Err(ContractError::WrongExpiration {});
+    }
+
+    check_treasury_availability(deps.as_ref(), &env, &msgs)?;
+
+    let (deposit, deposit_msgs) = match &cfg.deposit_info {
+        Some(deposit_info) => {
+            let msgs = take_deposit(&env, &info, deposit_info)?;
+            (
+                Some(ProposalDeposit {
+                    token: deposit_info.token.clone(),
+                    amount: deposit_info.amount,
+                    depositor: info.sender.clone(),
+                    refund_failed_proposals: deposit_info.refund_failed_proposals,
+                    refunded: false,
+                }),
+                msgs,
+            )
+        }
+        None => (None, vec![]),
+    };
+
+    // create a proposal
+    let mut prop = Proposal {
+        title,
+        description,
+        start_height: env.block.height,
+        expires,
+        msgs,
+        status: Status::Open,
+        votes: Votes::yes(vote_power),
+        threshold: cfg.threshold,
+        total_weight: cfg.total_weight,
+        deposit,
+        veto_threshold: cfg.veto_threshold,
+    };
+    prop.update_status(&env.block);
+    let id = next_id(deps.storage)?;
+    PROPOSALS.save(deps.storage, id, &prop)?;
+
+    // add the first yes vote from voter
+    let ballot = Ballot {
+        weight: vote_power,
+        vote: Vote::Yes,
+    };
+    BALLOTS.save(deps.storage, (id, &info.sender), &ballot)?;
+
+    Ok(Response::new()
+        .add_messages(deposit_msgs)
+        .add_attribute("action", "propose")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+/// Escrows the configured deposit from the proposer: native funds must be
+/// attached to the `Propose` call, cw20 funds are pulled in with a
+/// `TransferFrom` (the proposer must have set an allowance beforehand).
+fn take_deposit(
+    env: &Env,
+    info: &MessageInfo,
+    deposit_info: &CheckedDepositInfo,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    if deposit_info.amount.is_zero() {
+        return Ok(vec![]);
+    }
+    match &deposit_info.token {
+        DepositToken::Native { denom } => {
+            let paid = info
+                .funds
+                .iter()
+                .find(|c| &c.denom == denom)
+                .map(|c| c.amount)
+                .unwrap_or_default();
+            if paid != deposit_info.amount {
+                return Err(ContractError::InvalidDeposit {});
+            }
+            Ok(vec![])
+        }
+        DepositToken::Cw20 { address } => {
+            // the proposer must have set an allowance for this contract beforehand;
+            // the deposit is escrowed by pulling it into the multisig's own balance
+            Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: address.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: deposit_info.amount,
+                })?,
+                funds: vec![],
+            })])
+        }
+    }
+}
+
+/// Returns the message(s) needed to return an escrowed deposit to its
+/// depositor, if refunding is appropriate for the given terminal status and
+/// the deposit hasn't already been refunded.
+fn refund_deposit_msgs(deposit: &ProposalDeposit, status: Status) -> StdResult<Vec<CosmosMsg>> {
+    if deposit.refunded {
+        return Ok(vec![]);
+    }
+    let should_refund = match status {
+        Status::Executed => true,
+        Status::Rejected => deposit.refund_failed_proposals,
+        _ => false,
+    };
+    if !should_refund || deposit.amount.is_zero() {
+        return Ok(vec![]);
+    }
+    match &deposit.token {
+        DepositToken::Native { denom } => Ok(vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: deposit.depositor.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount: deposit.amount,
+            }],
+        })]),
+        DepositToken::Cw20 { address } => Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: address.clone(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deposit.depositor.to_string(),
+                amount: deposit.amount,
+            })?,
+            funds: vec![],
+        })]),
+    }
+}
+
+pub fn execute_propose_multiple(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    title: String,
+    description: String,
+    options: Vec<MultiOptionMsg>,
+    latest: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    // only members of the multisig can create a proposal
+    VOTERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    if options.len() < 2 {
+        return Err(ContractError::NotEnoughOptions {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let max_expires = cfg.max_voting_period.after(&env.block);
+    let mut expires = latest.unwrap_or(max_expires);
+    let comp = expires.partial_cmp(&max_expires);
+    if let Some(Ordering::Greater) = comp {
+        expires = max_expires;
+    } else if comp.is_none() {
+        return Err(ContractError::WrongExpiration {});
+    }
+
+    for option in &options {
+        check_treasury_availability(deps.as_ref(), &env, &option.msgs)?;
+    }
+
+    let options = options
+        .into_iter()
+        .map(|o| MultiOption {
+            title: o.title,
+            msgs: o.msgs,
+        })
+        .collect();
+
+    let prop = MultiProposal {
+        title,
+        description,
+        start_height: env.block.height,
+        expires,
+        options,
+        status: Status::Open,
+        quorum: cfg.multi_proposal_quorum,
+        total_weight: cfg.total_weight,
+        total_voted_weight: 0,
+    };
+    let id = next_id(deps.storage)?;
+    MULTI_PROPOSALS.save(deps.storage, id, &prop)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_multiple")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", id.to_string()))
+}
+
+pub fn execute_vote_ranked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    ranking: Vec<Vec<u32>>,
+) -> Result<Response, ContractError> {
+    let vote_power = VOTERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let mut prop = MULTI_PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.status != Status::Open {
+        return Err(ContractError::NotOpen {});
+    }
+    if prop.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    let num_options = prop.options.len() as u32;
+    if ranking.iter().flatten().any(|&opt| opt >= num_options) {
+        return Err(ContractError::InvalidRanking {});
+    }
+
+    let previous = RANKED_BALLOTS.may_load(deps.storage, (proposal_id, &info.sender))?;
+    if previous.is_none() {
+        prop.total_voted_weight += vote_power;
+    }
+    RANKED_BALLOTS.save(
+        deps.storage,
+        (proposal_id, &info.sender),
+        &RankedBallot {
+            weight: vote_power,
+            ranking,
+        },
+    )?;
+    MULTI_PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "vote_ranked")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_execute_multiple(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut prop = MULTI_PROPOSALS.load(deps.storage, proposal_id)?;
+    prop.update_status(&env.block);
+    if prop.status != Status::Passed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+
+    let ballots: Vec<(u64, Ranking)> = RANKED_BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, ballot) = item?;
+            Ok((ballot.weight, ballot.ranking))
+        })
+        .collect::<StdResult<_>>()?;
+    let winner = schulze_winner(prop.options.len(), &ballots);
+
+    prop.status = Status::Executed;
+    MULTI_PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_messages(prop.options[winner].msgs.clone())
+        .add_attribute("action", "execute_multiple")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("winning_option", winner.to_string()))
+}
+
+pub fn execute_close_multiple(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut prop = MULTI_PROPOSALS.load(deps.storage, proposal_id)?;
+    if !prop.expires.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
+    prop.update_status(&env.block);
+    if prop.status != Status::Rejected {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    MULTI_PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "close_multiple")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: Vote,
+) -> Result<Response, ContractError> {
+    // only members of the multisig can vote
+    let vote_power = VOTERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    // ensure proposal exists and can be voted on
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.status != Status::Open {
+        return Err(ContractError::NotOpen {});
+    }
+    if prop.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    // if the voter already has a ballot, back it out of the tally so it can
+    // be replaced below: this is what lets a voter change their vote
+    let previous_ballot = BALLOTS.may_load(deps.storage, (proposal_id, &info.sender))?;
+    if let Some(previous) = &previous_ballot {
+        prop.votes.subtract_vote(previous.vote, previous.weight);
+    }
+
+    BALLOTS.save(
+        deps.storage,
+        (proposal_id, &info.sender),
+        &Ballot {
+            weight: vote_power,
+            vote,
+        },
+    )?;
+
+    // update vote tally
+    prop.votes.add_vote(vote, vote_power);
+    prop.update_status(&env.block);
+    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    let action = if previous_ballot.is_some() {
+        "change_vote"
+    } else {
+        "vote"
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+pub fn execute_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    // anyone can trigger this if the vote passed
+
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    prop.update_status(&env.block);
+    if prop.status != Status::Passed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+
+    prop.status = Status::Executed;
+
+    let mut res = Response::new();
+    if let Some(deposit) = &mut prop.deposit {
+        let refund_msgs = refund_deposit_msgs(deposit, Status::Executed)?;
+        if !refund_msgs.is_empty() {
+            deposit.refunded = true;
+        }
+        res = res.add_messages(refund_msgs);
+    }
+    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    Ok(res
+        .add_messages(prop.msgs)
+        .add_attribute("action", "execute")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_close(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    // anyone can trigger this once the proposal's fate is certain: either it
+    // has expired, or it's already latched to Rejected early (e.g. vetoed or
+    // rejected by vote, which execute_vote persists as soon as it's certain,
+    // without itself refunding the deposit). A proposal already persisted as
+    // Rejected may therefore still need to be re-entered here to flush that
+    // refund.
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if [Status::Executed, Status::Passed].iter().any(|s| s == &prop.status) {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    if prop.status == Status::Rejected {
+        let deposit_pending = prop
+            .deposit
+            .as_ref()
+            .map_or(false, |deposit| !deposit.refunded);
+        if !deposit_pending {
+            return Err(ContractError::WrongCloseStatus {});
+        }
+    } else if prop.current_status(&env.block) != Status::Rejected {
+        return Err(ContractError::NotExpired {});
+    }
+
+    prop.status = Status::Rejected;
+
+    let mut res = Response::new();
+    if let Some(deposit) = &mut prop.deposit {
+        let refund_msgs = refund_deposit_msgs(deposit, Status::Rejected)?;
+        if !refund_msgs.is_empty() {
+            deposit.refunded = true;
+        }
+        res = res.add_messages(refund_msgs);
+    }
+    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+
+    Ok(res
+        .add_attribute("action", "close")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Threshold {} => to_binary(&query_threshold(deps)?),
+        QueryMsg::Proposal { proposal_id } => to_binary(&query_proposal(deps, env, proposal_id)?),
+        QueryMsg::Vote { proposal_id, voter } => to_binary(&query_vote(deps, proposal_id, voter)?),
+        QueryMsg::ListProposals { start_after, limit } => {
+            to_binary(&list_proposals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::ReverseProposals {
+            start_before,
+            limit,
+        } => to_binary(&reverse_proposals(deps, env, start_before, limit)?),
+        QueryMsg::ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&list_votes(deps, proposal_id, start_after, limit)?),
+        QueryMsg::Voter { address } => to_binary(&query_voter(deps, address)?),
+        QueryMsg::ListVoters { start_after, limit } => {
+            to_binary(&list_voters(deps, start_after, limit)?)
+        }
+        QueryMsg::DepositInfo { proposal_id } => {
+            to_binary(&query_deposit_info(deps, proposal_id)?)
+        }
+        QueryMsg::Treasury {} => to_binary(&query_treasury(deps, env)?),
+        QueryMsg::MultiProposal { proposal_id } => {
+            to_binary(&query_multi_proposal(deps, env, proposal_id)?)
+        }
+        QueryMsg::MultiProposalWinner { proposal_id } => {
+            to_binary(&query_multi_proposal_winner(deps, env, proposal_id)?)
+        }
+    }
+}
+
+fn query_multi_proposal(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<MultiProposalResponse> {
+    let prop = MULTI_PROPOSALS.load(deps.storage, proposal_id)?;
+    Ok(MultiProposalResponse {
+        id: proposal_id,
+        title: prop.title,
+        description: prop.description,
+        option_titles: prop.options.iter().map(|o| o.title.clone()).collect(),
+        status: prop.current_status(&env.block),
+        expires: prop.expires,
+    })
+}
+
+/// Returns the Schulze winner's option index once the proposal has expired
+/// and received at least one ballot, `None` otherwise.
+fn query_multi_proposal_winner(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<Option<u32>> {
+    let prop = MULTI_PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) == Status::Open {
+        return Ok(None);
+    }
+
+    let ballots: Vec<(u64, Ranking)> = RANKED_BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, ballot) = item?;
+            Ok((ballot.weight, ballot.ranking))
+        })
+        .collect::<StdResult<_>>()?;
+    if ballots.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(schulze_winner(prop.options.len(), &ballots) as u32))
+}
+
+fn query_treasury(deps: Deps, env: Env) -> StdResult<TreasuryResponse> {
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+    let reserved = reserved_funds(deps, &env.block)?;
+    let escrowed_deposits = outstanding_deposits(deps, &env.block)?;
+    Ok(TreasuryResponse {
+        balances,
+        reserved,
+        escrowed_deposits,
+    })
+}
+
+/// True while `deposit`'s native coins are still sitting in the contract's
+/// balance without being spendable: they haven't been refunded yet, and the
+/// proposal hasn't reached a terminal state that permanently forfeits them
+/// to the treasury (a Rejected proposal with `refund_failed_proposals` unset
+/// keeps its deposit for good, at which point it's just ordinary balance).
+fn deposit_outstanding(prop: &Proposal, block: &cosmwasm_std::BlockInfo) -> bool {
+    let Some(deposit) = &prop.deposit else {
+        return false;
+    };
+    if deposit.refunded || deposit.amount.is_zero() {
+        return false;
+    }
+    let forfeited =
+        prop.current_status(block) == Status::Rejected && !deposit.refund_failed_proposals;
+    !forfeited
+}
+
+/// Sums native deposits still escrowed against their proposals, per
+/// `deposit_outstanding`, so they can be excluded from what's actually
+/// available to spend.
+fn outstanding_deposits(deps: Deps, block: &cosmwasm_std::BlockInfo) -> StdResult<Vec<Coin>> {
+    let mut outstanding: Vec<Coin> = vec![];
+    for item in PROPOSALS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, prop) = item?;
+        if !deposit_outstanding(&prop, block) {
+            continue;
+        }
+        if let Some(deposit) = &prop.deposit {
+            if let DepositToken::Native { denom } = &deposit.token {
+                add_coin(
+                    &mut outstanding,
+                    &Coin {
+                        denom: denom.clone(),
+                        amount: deposit.amount,
+                    },
+                );
+            }
+        }
+    }
+    Ok(outstanding)
+}
+
+/// Sums the native coins committed to `BankMsg::Send`s in proposals that have
+/// passed but not yet been executed, so the amount isn't double-counted
+/// against the treasury's actual balance.
+fn reserved_funds(deps: Deps, block: &cosmwasm_std::BlockInfo) -> StdResult<Vec<Coin>> {
+    let mut reserved: Vec<Coin> = vec![];
+    for item in PROPOSALS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, prop) = item?;
+        if prop.current_status(block) != Status::Passed {
+            continue;
+        }
+        for coin in bank_send_totals(&prop.msgs) {
+            add_coin(&mut reserved, &coin);
+        }
+    }
+    for item in MULTI_PROPOSALS.range(deps.storage, None, None, Order::Ascending) {
+        let (id, prop) = item?;
+        if prop.current_status(block) != Status::Passed {
+            continue;
+        }
+        // a multi-proposal only settles to Passed once it has expired, so by
+        // then its ballots are final and the Schulze winner is already decided
+        let ballots: Vec<(u64, Ranking)> = RANKED_BALLOTS
+            .prefix(id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, ballot) = item?;
+                Ok((ballot.weight, ballot.ranking))
+            })
+            .collect::<StdResult<_>>()?;
+        if ballots.is_empty() {
+            continue;
+        }
+        let winner = schulze_winner(prop.options.len(), &ballots);
+        for coin in bank_send_totals(&prop.options[winner].msgs) {
+            add_coin(&mut reserved, &coin);
+        }
+    }
+    Ok(reserved)
+}
+
+/// Totals the native coins moved by `BankMsg::Send`s among `msgs`.
+fn bank_send_totals(msgs: &[CosmosMsg<Empty>]) -> Vec<Coin> {
+    let mut totals: Vec<Coin> = vec![];
+    for msg in msgs {
+        if let CosmosMsg::Bank(BankMsg::Send { amount, .. }) = msg {
+            for coin in amount {
+                add_coin(&mut totals, coin);
+            }
+        }
+    }
+    totals
+}
+
+fn add_coin(coins: &mut Vec<Coin>, coin: &Coin) {
+    match coins.iter_mut().find(|c| c.denom == coin.denom) {
+        Some(existing) => existing.amount += coin.amount,
+        None => coins.push(coin.clone()),
+    }
+}
+
+/// Rejects a proposal's `BankMsg::Send`s up front if they'd commit more of a
+/// denom than the treasury actually has left once funds already reserved by
+/// other passed-but-unexecuted proposals are set aside, so proposers can't
+/// double-commit the same coins across concurrent proposals.
+fn check_treasury_availability(
+    deps: Deps,
+    env: &Env,
+    msgs: &[CosmosMsg<Empty>],
+) -> Result<(), ContractError> {
+    let requested = bank_send_totals(msgs);
+    if requested.is_empty() {
+        return Ok(());
+    }
+
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+    let reserved = reserved_funds(deps, &env.block)?;
+    let escrowed_deposits = outstanding_deposits(deps, &env.block)?;
+    for coin in &requested {
+        let available = balances
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        let already_reserved = reserved
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        let already_escrowed = escrowed_deposits
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        let spendable = available
+            .saturating_sub(already_reserved)
+            .saturating_sub(already_escrowed);
+        if coin.amount > spendable {
+            return Err(ContractError::InsufficientTreasuryFunds {});
+        }
+    }
+    Ok(())
+}
+
+fn query_threshold(deps: Deps) -> StdResult<ThresholdResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(cfg.threshold.to_response(cfg.total_weight))
+}
+
+fn query_proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse> {
+    let prop = PROPOSALS.load(deps.storage, id)?;
+    let status = prop.current_status(&env.block);
+    let threshold = prop.threshold.to_response(prop.total_weight);
+    Ok(ProposalResponse {
+        id,
+        title: prop.title,
+        description: prop.description,
+        msgs: prop.msgs,
+        status,
+        expires: prop.expires,
+        threshold,
+    })
+}
+
+fn query_deposit_info(deps: Deps, proposal_id: u64) -> StdResult<Option<ProposalDeposit>> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    Ok(prop.deposit)
+}
+
+fn list_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let min = start_after.map(Bound::exclusive);
+    let props: StdResult<Vec<_>> = PROPOSALS
+        .range(deps.storage, min, None, Order::Ascending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect();
+
+    Ok(ProposalListResponse { proposals: props? })
+}
+
+fn reverse_proposals(
+    deps: Deps,
+    env: Env,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let max = start_before.map(Bound::exclusive);
+    let props: StdResult<Vec<_>> = PROPOSALS
+        .range(deps.storage, None, max, Order::Descending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect();
+
+    Ok(ProposalListResponse { proposals: props? })
+}
+
+fn map_proposal(
+    block: &cosmwasm_std::BlockInfo,
+    item: StdResult<(u64, Proposal)>,
+) -> StdResult<ProposalResponse> {
+    item.map(|(id, prop)| {
+        let status = prop.current_status(block);
+        let threshold = prop.threshold.to_response(prop.total_weight);
+        ProposalResponse {
+            id,
+            title: prop.title,
+            description: prop.description,
+            msgs: prop.msgs,
+            status,
+            expires: prop.expires,
+            threshold,
+        }
+    })
+}
+
+fn query_vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
+    let voter_addr = deps.api.addr_validate(&voter)?;
+    let ballot = BALLOTS.may_load(deps.storage, (proposal_id, &voter_addr))?;
+    let vote = ballot.map(|b| VoteInfo {
+        proposal_id,
+        voter,
+        vote: b.vote,
+        weight: b.weight,
+    });
+    Ok(VoteResponse { vote })
+}
+
+fn list_votes(
+    deps: Deps,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VoteListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let min = addr.as_ref().map(Bound::exclusive);
+
+    let votes: StdResult<Vec<_>> = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, min, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (voter, ballot) = item?;
+            Ok(VoteInfo {
+                proposal_id,
+                voter: voter.into_string(),
+                vote: ballot.vote,
+                weight: ballot.weight,
+            })
+        })
+        .collect();
+
+    Ok(VoteListResponse { votes: votes? })
+}
+
+fn query_voter(deps: Deps, voter: String) -> StdResult<VoterResponse> {
+    let voter_addr = deps.api.addr_validate(&voter)?;
+    let weight = VOTERS.may_load(deps.storage, &voter_addr)?;
+    Ok(VoterResponse { weight })
+}
+
+fn list_voters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VoterListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let min = addr.as_ref().map(Bound::exclusive);
+
+    let voters: StdResult<Vec<_>> = VOTERS
+        .range(deps.storage, min, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (addr, weight) = item?;
+            Ok(VoterDetail {
+                addr: addr.into_string(),
+                weight,
+            })
+        })
+        .collect();
+
+    Ok(VoterListResponse { voters: voters? })
+}
@@ -0,0 +1,57 @@
+use cosmwasm_std::StdError;
+use cw_utils::{PaymentError, ThresholdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Threshold(#[from] ThresholdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Must have at least one voter")]
+    NoVoters {},
+
+    #[error("Proposal is not open")]
+    NotOpen {},
+
+    #[error("Proposal voting period has expired")]
+    Expired {},
+
+    #[error("Proposal must expire before you can close it")]
+    NotExpired {},
+
+    #[error("Wrong expiration option")]
+    WrongExpiration {},
+
+    #[error("Proposal must have passed and not yet been executed")]
+    WrongExecuteStatus {},
+
+    #[error("Cannot close completed or passed proposals")]
+    WrongCloseStatus {},
+
+    #[error("Proposal deposit does not match the amount required by this multisig")]
+    InvalidDeposit {},
+
+    #[error("This multisig does not require a proposal deposit")]
+    NoDepositRequired {},
+
+    #[error("Veto threshold must be greater than 0 and at most 1")]
+    InvalidVetoThreshold {},
+
+    #[error("A multiple-choice proposal needs at least two options")]
+    NotEnoughOptions {},
+
+    #[error("Ranked ballot refers to an option that does not exist on this proposal")]
+    InvalidRanking {},
+
+    #[error("Proposal would send more than the treasury's unreserved balance")]
+    InsufficientTreasuryFunds {},
+}
@@ -74,6 +74,9 @@ fn cw3_controls_cw20() {
         ],
         threshold: Threshold::AbsoluteCount { weight: 2 },
         max_voting_period: Duration::Height(3),
+        deposit_info: None,
+        veto_threshold: None,
+        multi_proposal_quorum: None,
     };
 
     let multisig_addr = router
@@ -211,6 +214,9 @@ fn cw3_3_of_5_multisig() {
         ],
         threshold: Threshold::AbsoluteCount { weight: 3 },
         max_voting_period: Duration::Height(3),
+        deposit_info: None,
+        veto_threshold: None,
+        multi_proposal_quorum: None,
     };
 
     let multisig_addr = router
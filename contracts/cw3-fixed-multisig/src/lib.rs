@@ -0,0 +1,10 @@
+pub mod contract;
+mod error;
+pub mod msg;
+mod schulze;
+pub mod state;
+
+#[cfg(test)]
+mod integration_tests;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,181 @@
+use cosmwasm_std::{Coin, CosmosMsg, Decimal, Empty, Uint128};
+use cw3::{Status, Vote};
+use cw_utils::{Duration, Expiration, Threshold};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub voters: Vec<Voter>,
+    pub threshold: Threshold,
+    pub max_voting_period: Duration,
+    /// Optional deposit that must be escrowed by a proposer when creating a new
+    /// proposal, refunded on execution or rejection (depending on
+    /// `refund_failed_proposals`). Left unset, proposing is free.
+    pub deposit_info: Option<DepositInfo>,
+    /// Share of the total registered weight (0, 1] that, once voted Veto,
+    /// forces a proposal to `Rejected` even before expiry or quorum.
+    pub veto_threshold: Option<Decimal>,
+    /// Share of the total registered weight that must vote on a
+    /// multiple-choice proposal before its Schulze-method winner is final.
+    /// Left unset, multiple-choice proposals have no quorum requirement.
+    pub multi_proposal_quorum: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Voter {
+    pub addr: String,
+    pub weight: u64,
+}
+
+/// The token a proposal deposit is denominated in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum DepositToken {
+    Native { denom: String },
+    Cw20 { address: String },
+}
+
+/// Deposit configuration for new proposals, mirroring the pre-propose deposit
+/// pattern used by dao-contracts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositInfo {
+    pub token: DepositToken,
+    pub amount: Uint128,
+    /// If true, a deposit is refunded when its proposal is closed as
+    /// rejected. If false, the deposit stays with the multisig.
+    pub refund_failed_proposals: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Propose {
+        title: String,
+        description: String,
+        msgs: Vec<CosmosMsg<Empty>>,
+        latest: Option<Expiration>,
+    },
+    /// Casts a ballot for `proposal_id`. While the proposal is still open, a
+    /// voter may call this again to overwrite their earlier ballot.
+    Vote {
+        proposal_id: u64,
+        vote: Vote,
+    },
+    Execute {
+        proposal_id: u64,
+    },
+    Close {
+        proposal_id: u64,
+    },
+    /// Accepts native funds into the multisig's treasury. Anyone may call
+    /// this; the attached `funds` are credited to the contract's own bank
+    /// balance like any other `MsgExecuteContract`.
+    Deposit {},
+    /// Creates a multiple-choice proposal: voters rank `options` instead of
+    /// casting a single Yes/No, and the winner is resolved with the Schulze
+    /// method once the proposal expires.
+    ProposeMultiple {
+        title: String,
+        description: String,
+        options: Vec<MultiOptionMsg>,
+        latest: Option<Expiration>,
+    },
+    /// Casts a ranked ballot for a multiple-choice proposal. `ranking` lists
+    /// groups of option indices from most to least preferred; options tied
+    /// within a group contribute to neither's pairwise count against the
+    /// other, and any option index missing from `ranking` is treated as tied
+    /// for last place.
+    VoteRanked {
+        proposal_id: u64,
+        ranking: Vec<Vec<u32>>,
+    },
+    ExecuteMultiple {
+        proposal_id: u64,
+    },
+    CloseMultiple {
+        proposal_id: u64,
+    },
+}
+
+/// One option of a multiple-choice proposal, as supplied by the proposer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiOptionMsg {
+    pub title: String,
+    pub msgs: Vec<CosmosMsg<Empty>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Threshold {},
+    Proposal {
+        proposal_id: u64,
+    },
+    ListProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ReverseProposals {
+        start_before: Option<u64>,
+        limit: Option<u32>,
+    },
+    Vote {
+        proposal_id: u64,
+        voter: String,
+    },
+    ListVotes {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Voter {
+        address: String,
+    },
+    ListVoters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the deposit taken for a given proposal (token, amount,
+    /// depositor, refund terms) and whether it has been refunded yet, or
+    /// `None` if the proposal was created without a deposit.
+    DepositInfo {
+        proposal_id: u64,
+    },
+    /// Returns the treasury's current native balances, alongside the amounts
+    /// already reserved by proposals that have passed but not yet executed.
+    Treasury {},
+    MultiProposal {
+        proposal_id: u64,
+    },
+    /// Returns the Schulze winner's option index, or `None` if the proposal
+    /// hasn't expired yet or has no ballots.
+    MultiProposalWinner {
+        proposal_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiProposalResponse {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub option_titles: Vec<String>,
+    pub status: Status,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    /// The multisig's current native bank balances, including any escrowed
+    /// proposal deposits (the chain credits deposit funds before the
+    /// `Propose` entrypoint runs, so they are indistinguishable from other
+    /// funds in the raw balance).
+    pub balances: Vec<Coin>,
+    /// Amounts already committed to `BankMsg::Send`s in proposals that have
+    /// passed but not yet been executed, so proposers don't double-commit
+    /// the same coins across concurrent proposals.
+    pub reserved: Vec<Coin>,
+    /// Amounts still escrowed as native proposal deposits that may yet be
+    /// refunded, already included in `balances` but not actually spendable.
+    pub escrowed_deposits: Vec<Coin>,
+}
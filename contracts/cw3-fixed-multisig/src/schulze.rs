@@ -0,0 +1,65 @@
+//! The Schulze method: a Condorcet-consistent way to pick a winner among more
+//! than two options from voters' ranked preferences, used to resolve
+//! cw3-fixed-multisig's multiple-choice proposals.
+
+/// One voter's ranking: groups of tied option indices, most preferred group
+/// first. An option index absent from every group is tied for last place.
+pub type Ranking = Vec<Vec<u32>>;
+
+/// Computes the Schulze winner among `num_options` options from a set of
+/// `(weight, ranking)` ballots.
+///
+/// Builds the pairwise preference matrix `d[i][j]` (weighted voters ranking
+/// `i` strictly above `j`), then the strongest-path matrix `p` via
+/// Floyd-Warshall, and returns the option `i` for which `p[i][j] >= p[j][i]`
+/// holds against every other option `j`. If several options satisfy that
+/// (no unique Condorcet winner), the lowest index among them wins.
+pub fn schulze_winner(num_options: usize, ballots: &[(u64, Ranking)]) -> usize {
+    assert!(num_options > 0, "a proposal must have at least one option");
+    let n = num_options;
+    let mut d = vec![vec![0u64; n]; n];
+
+    for (weight, ranking) in ballots {
+        // rank[i] is the position of option i, lower meaning more preferred;
+        // options absent from the ranking are tied for last place.
+        let mut rank = vec![ranking.len(); n];
+        for (position, tied_group) in ranking.iter().enumerate() {
+            for &option in tied_group {
+                rank[option as usize] = position;
+            }
+        }
+        for i in 0..n {
+            for j in 0..n {
+                if i != j && rank[i] < rank[j] {
+                    d[i][j] += weight;
+                }
+            }
+        }
+    }
+
+    let mut p = vec![vec![0u64; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && d[i][j] > d[j][i] {
+                p[i][j] = d[i][j];
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            if i == k {
+                continue;
+            }
+            for j in 0..n {
+                if j == k || j == i {
+                    continue;
+                }
+                p[i][j] = p[i][j].max(p[i][k].min(p[k][j]));
+            }
+        }
+    }
+
+    (0..n)
+        .find(|&i| (0..n).all(|j| i == j || p[i][j] >= p[j][i]))
+        .unwrap_or(0)
+}
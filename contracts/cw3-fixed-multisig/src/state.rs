@@ -0,0 +1,306 @@
+use cosmwasm_std::{Addr, BlockInfo, CosmosMsg, Decimal, Empty, StdResult, Storage, Uint128};
+use cw3::{Status, Vote};
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration, Threshold};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::DepositToken;
+use crate::schulze::Ranking;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub threshold: Threshold,
+    pub total_weight: u64,
+    pub max_voting_period: Duration,
+    pub deposit_info: Option<CheckedDepositInfo>,
+    /// Share of the total registered weight that, once voted Veto, forces a
+    /// proposal to `Rejected` regardless of the yes/no tally or quorum.
+    pub veto_threshold: Option<Decimal>,
+    /// Quorum required before a multiple-choice proposal's Schulze winner is
+    /// final. `None` means multiple-choice proposals have no quorum.
+    pub multi_proposal_quorum: Option<Decimal>,
+}
+
+/// `DepositInfo` after its token address has been validated, ready to be
+/// attached to a proposal at the moment it is created.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CheckedDepositInfo {
+    pub token: DepositToken,
+    pub amount: Uint128,
+    pub refund_failed_proposals: bool,
+}
+
+/// The deposit actually escrowed for a single proposal. Proposals keep their
+/// own copy rather than reading `Config` at refund time, so that a deposit
+/// taken under one configuration is always refunded on the same terms even if
+/// the multisig's deposit settings were to change later.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalDeposit {
+    pub token: DepositToken,
+    pub amount: Uint128,
+    pub depositor: Addr,
+    pub refund_failed_proposals: bool,
+    /// Set once a refund message for this deposit has actually been issued,
+    /// so it is never refunded twice and `DepositInfo` queries can report
+    /// whether the deposit is still escrowed.
+    pub refunded: bool,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const VOTERS: Map<&Addr, u64> = Map::new("voters");
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+// multiple-choice proposals share the PROPOSAL_COUNT sequence with `PROPOSALS`
+// so that proposal ids stay globally unique, but live in their own map since
+// their shape (N options, ranked ballots) doesn't fit `Proposal`/`Ballot`.
+pub const MULTI_PROPOSALS: Map<u64, MultiProposal> = Map::new("multi_proposals");
+pub const RANKED_BALLOTS: Map<(u64, &Addr), RankedBallot> = Map::new("ranked_ballots");
+
+pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
+    let id: u64 = PROPOSAL_COUNT.may_load(store)?.unwrap_or_default() + 1;
+    PROPOSAL_COUNT.save(store, &id)?;
+    Ok(id)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub title: String,
+    pub description: String,
+    pub start_height: u64,
+    pub expires: Expiration,
+    pub msgs: Vec<CosmosMsg<Empty>>,
+    pub status: Status,
+    pub votes: Votes,
+    pub threshold: Threshold,
+    pub total_weight: u64,
+    pub deposit: Option<ProposalDeposit>,
+    pub veto_threshold: Option<Decimal>,
+}
+
+impl Proposal {
+    /// Recomputes status against the current block, without persisting it.
+    /// `Open` proposals latch to `Passed` the moment the threshold is
+    /// reachable, to `Rejected` the moment it no longer can be (whichever
+    /// voters remain can't push yes votes over the line) or once expired
+    /// without having passed, and are rejected outright the moment veto votes
+    /// cross `veto_threshold`. This lets `Execute`/`Close` settle a proposal
+    /// as soon as its outcome is certain, instead of waiting out the full
+    /// voting period.
+    pub fn current_status(&self, block: &BlockInfo) -> Status {
+        let mut status = self.status.clone();
+
+        if status == Status::Open && self.is_vetoed() {
+            status = Status::Rejected;
+        }
+        if status == Status::Open && self.is_passed(block) {
+            status = Status::Passed;
+        }
+        if status == Status::Open && self.is_rejected() {
+            status = Status::Rejected;
+        }
+        if status == Status::Open && self.expires.is_expired(block) {
+            status = Status::Rejected;
+        }
+
+        status
+    }
+
+    /// True once the remaining, not-yet-cast weight can no longer tip the
+    /// proposal into passing, even if every last voter votes yes. For the
+    /// percentage-based thresholds this uses the total weight less the
+    /// current abstain count as the denominator, which is the correct worst
+    /// case: letting a remaining
+    /// voter abstain instead of voting yes can only shrink the needed-yes
+    /// bar by at most their own weight (since the percentage is at most
+    /// 1.0), so it's never better for passing than just having them vote
+    /// yes, which is exactly the scenario `max_possible_yes` already checks.
+    pub fn is_rejected(&self) -> bool {
+        let max_possible_yes = self.votes.yes + (self.total_weight - self.votes.total());
+        match self.threshold {
+            Threshold::AbsoluteCount {
+                weight: weight_needed,
+            } => max_possible_yes < weight_needed,
+            Threshold::AbsolutePercentage {
+                percentage: percentage_needed,
+            } => {
+                max_possible_yes
+                    < votes_needed(self.total_weight - self.votes.abstain, percentage_needed)
+            }
+            Threshold::ThresholdQuorum { threshold, .. } => {
+                let opinions = self.total_weight - self.votes.abstain;
+                opinions != 0 && max_possible_yes < votes_needed(opinions, threshold)
+            }
+        }
+    }
+
+    /// True once Veto votes alone cross the configured veto threshold of the
+    /// total registered weight.
+    pub fn is_vetoed(&self) -> bool {
+        match self.veto_threshold {
+            Some(veto_threshold) => {
+                self.votes.veto >= votes_needed(self.total_weight, veto_threshold)
+            }
+            None => false,
+        }
+    }
+
+    /// Sets `self.status` to the result of `current_status`.
+    pub fn update_status(&mut self, block: &BlockInfo) {
+        self.status = self.current_status(block);
+    }
+
+    pub fn is_passed(&self, block: &BlockInfo) -> bool {
+        match self.threshold {
+            Threshold::AbsoluteCount {
+                weight: weight_needed,
+            } => self.votes.yes >= weight_needed,
+            Threshold::AbsolutePercentage {
+                percentage: percentage_needed,
+            } => {
+                self.votes.yes
+                    >= votes_needed(self.total_weight - self.votes.abstain, percentage_needed)
+            }
+            Threshold::ThresholdQuorum { threshold, quorum } => {
+                if self.votes.total() < votes_needed(self.total_weight, quorum) {
+                    return false;
+                }
+                if self.expires.is_expired(block) {
+                    let opinions = self.votes.total() - self.votes.abstain;
+                    opinions != 0 && self.votes.yes >= votes_needed(opinions, threshold)
+                } else {
+                    let opinions = self.total_weight - self.votes.abstain;
+                    opinions != 0 && self.votes.yes >= votes_needed(opinions, threshold)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Votes {
+    pub yes: u64,
+    pub no: u64,
+    pub abstain: u64,
+    pub veto: u64,
+}
+
+impl Votes {
+    /// Creates the initial tally for a proposal, counting the proposer's
+    /// auto-cast Yes vote.
+    pub fn yes(init_weight: u64) -> Self {
+        Votes {
+            yes: init_weight,
+            no: 0,
+            abstain: 0,
+            veto: 0,
+        }
+    }
+
+    pub fn add_vote(&mut self, vote: Vote, weight: u64) {
+        match vote {
+            Vote::Yes => self.yes += weight,
+            Vote::No => self.no += weight,
+            Vote::Abstain => self.abstain += weight,
+            Vote::Veto => self.veto += weight,
+        }
+    }
+
+    /// Reverses `add_vote`, used to back out a ballot being overwritten by a
+    /// repeat `Vote` before the new one is tallied in.
+    pub fn subtract_vote(&mut self, vote: Vote, weight: u64) {
+        match vote {
+            Vote::Yes => self.yes -= weight,
+            Vote::No => self.no -= weight,
+            Vote::Abstain => self.abstain -= weight,
+            Vote::Veto => self.veto -= weight,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.yes + self.no + self.abstain + self.veto
+    }
+}
+
+// this is a helper function so Decimal works with u64 rather than Uint128
+// also, we must *round up* here, as we need 212 votes if the threshold is 51% and total_weight is 415
+pub fn votes_needed(weight: u64, percentage: Decimal) -> u64 {
+    let fractional = Uint128::new(10u128.pow(Decimal::DECIMAL_PLACES));
+    let applied = Uint128::from(weight) * percentage.atomics() + fractional - Uint128::new(1);
+    (applied / fractional).u128() as u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ballot {
+    pub weight: u64,
+    pub vote: Vote,
+}
+
+/// One option of a multiple-choice proposal, bound to the messages it would
+/// dispatch if it wins.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiOption {
+    pub title: String,
+    pub msgs: Vec<CosmosMsg<Empty>>,
+}
+
+/// A multiple-choice proposal resolved by ranked (Schulze-method) voting
+/// rather than a single Yes/No tally. Unlike `Proposal`, its status can't be
+/// latched early: a late ballot can still change the Condorcet winner, so it
+/// stays `Open` until it expires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiProposal {
+    pub title: String,
+    pub description: String,
+    pub start_height: u64,
+    pub expires: Expiration,
+    pub options: Vec<MultiOption>,
+    pub status: Status,
+    pub quorum: Option<Decimal>,
+    pub total_weight: u64,
+    pub total_voted_weight: u64,
+}
+
+impl MultiProposal {
+    /// Recomputes status against the current block, without persisting it.
+    /// Resolves to `Passed` once expired if quorum was met, `Rejected`
+    /// otherwise.
+    pub fn current_status(&self, block: &BlockInfo) -> Status {
+        let mut status = self.status.clone();
+        if status == Status::Open && self.expires.is_expired(block) {
+            status = if self.quorum_met() {
+                Status::Passed
+            } else {
+                Status::Rejected
+            };
+        }
+        status
+    }
+
+    /// Sets `self.status` to the result of `current_status`.
+    pub fn update_status(&mut self, block: &BlockInfo) {
+        self.status = self.current_status(block);
+    }
+
+    /// At least one ballot must have been cast, regardless of `quorum`: with
+    /// no quorum configured, an expired proposal with zero ballots would
+    /// otherwise resolve to `Passed` and dispatch an arbitrary option (the
+    /// Schulze winner of no ballots) that nobody actually voted for.
+    pub fn quorum_met(&self) -> bool {
+        if self.total_voted_weight == 0 {
+            return false;
+        }
+        match self.quorum {
+            Some(quorum) => self.total_voted_weight >= votes_needed(self.total_weight, quorum),
+            None => true,
+        }
+    }
+}
+
+/// A single voter's ranked ballot on a multiple-choice proposal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RankedBallot {
+    pub weight: u64,
+    pub ranking: Ranking,
+}